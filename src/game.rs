@@ -0,0 +1,521 @@
+use crate::mutations::{board_tiles, place_tiles_best};
+use crate::structures::{Board, Color, Player, Tile};
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+/// Number of tiles each player starts with.
+pub const HAND_SIZE: usize = 14;
+
+/// The minimum value of newly played tiles required for a player's first
+/// placement (the "opening meld"), per the standard Rummikub rules.
+pub const OPENING_MELD_MINIMUM: u32 = 30;
+
+/// How many Monte Carlo playouts `play_turn` averages over when deciding
+/// whether a placement is actually worth making. See [`monte_carlo_best_move`].
+///
+/// Each playout turn re-solves the board via `solve_maximal`, so this is
+/// kept modest: a full solo game already re-solves thousands of times over
+/// the course of its turns.
+pub const DEFAULT_PLAYOUTS: u32 = 4;
+
+/// Every tile in a standard Rummikub set: two copies of every color/value
+/// combination plus two jokers.
+fn standard_tile_set() -> Vec<Tile> {
+    let mut tiles = Vec::with_capacity(106);
+
+    for color in Color::ALL {
+        for value in 1..=13 {
+            tiles.push(Tile::Numbered { color, value });
+            tiles.push(Tile::Numbered { color, value });
+        }
+    }
+    tiles.push(Tile::Joker { standing_in: None });
+    tiles.push(Tile::Joker { standing_in: None });
+
+    tiles
+}
+
+/// Every tile not already accounted for by the board or `hand`: the pool a
+/// Monte Carlo playout draws from to model the rest of the bag and every
+/// other player's hand as one shared pool of unseen tiles.
+fn unseen_tiles(board: &Board, hand: &[Tile]) -> Vec<Tile> {
+    let mut pool = standard_tile_set();
+
+    for tile in board_tiles(board).iter().chain(hand) {
+        if let Some(position) = pool.iter().position(|t| t == tile) {
+            pool.remove(position);
+        }
+    }
+
+    pool
+}
+
+/// The shared pool of tiles a game draws from: two copies of every
+/// color/value combination plus two jokers, as in a physical Rummikub set.
+///
+/// # Example
+/// ```
+/// // new() -> 106 tiles: 2 * (13 values * 4 colors) + 2 jokers
+/// ```
+pub struct Bag {
+    tiles: Vec<Tile>,
+}
+
+impl Bag {
+    /// Build a freshly shuffled standard pool.
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> Bag {
+        let mut tiles = standard_tile_set();
+        tiles.shuffle(rng);
+        Bag { tiles }
+    }
+
+    /// Draw a single tile from the bag, if any remain.
+    pub fn draw(&mut self) -> Option<Tile> {
+        self.tiles.pop()
+    }
+
+    /// Deal `HAND_SIZE` tiles to a fresh player.
+    ///
+    /// # Returns
+    /// * `Some(Player)` - A player holding `HAND_SIZE` freshly drawn tiles
+    /// * `None` - If the bag doesn't hold enough tiles left to deal a full hand
+    pub fn deal_hand(&mut self) -> Option<Player> {
+        let mut tiles = Vec::with_capacity(HAND_SIZE);
+        for _ in 0..HAND_SIZE {
+            tiles.push(self.draw()?);
+        }
+        Some(Player { tiles })
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
+/// Number of opponents `Game::new` deals in alongside the human/primary
+/// player when the caller doesn't need a different table size.
+pub const DEFAULT_PLAYER_COUNT: usize = 4;
+
+/// A full multi-player game: a shared board and bag, one hand per player,
+/// and whose turn it is, rotating in seat order.
+///
+/// Each player's hand is tracked separately (unlike [`play_turn`]'s
+/// `unseen_tiles`, which still folds the bag and every *other* hand into one
+/// pool from the acting player's point of view, since a real player can't
+/// see their opponents' tiles).
+pub struct Game {
+    pub board: Board,
+    bag: Bag,
+    players: Vec<Player>,
+    melded: Vec<bool>,
+    next: usize,
+}
+
+/// What happened when a `Game` advanced by one turn. The resulting board, if
+/// it changed, is always available afterwards as `Game.board`.
+pub enum StepResult {
+    /// `player` placed `placed` tiles.
+    Played { player: usize, placed: Vec<Tile> },
+    /// `player` drew `tile` instead of placing.
+    Drew { player: usize, tile: Tile },
+    /// `player` could neither place nor draw (the bag is empty); their turn
+    /// is skipped.
+    Passed { player: usize },
+    /// `player` emptied their hand and won the game.
+    Won { player: usize },
+}
+
+impl Game {
+    /// Shuffle a fresh bag and deal a full hand to each of `num_players`
+    /// players, seat order 0..num_players.
+    ///
+    /// # Returns
+    /// * `Some(Game)` - A game ready to play, starting with seat 0
+    /// * `None` - If the bag doesn't hold enough tiles to deal every hand
+    pub fn new<R: Rng + ?Sized>(num_players: usize, rng: &mut R) -> Option<Game> {
+        let mut bag = Bag::new(rng);
+        let mut players = Vec::with_capacity(num_players);
+        for _ in 0..num_players {
+            players.push(bag.deal_hand()?);
+        }
+
+        Some(Game {
+            board: Board { groups: vec![], runs: vec![] },
+            bag,
+            melded: vec![false; num_players],
+            players,
+            next: 0,
+        })
+    }
+
+    /// How many tiles remain undrawn in the bag.
+    pub fn tiles_left(&self) -> usize {
+        self.bag.len()
+    }
+
+    /// Play the next seat's turn (see [`play_turn`]) and rotate to the seat
+    /// after it.
+    pub fn step<R: Rng + ?Sized>(&mut self, playouts: u32, rng: &mut R) -> StepResult {
+        let player = self.next;
+        self.next = (self.next + 1) % self.players.len();
+
+        match play_turn(&self.board, &mut self.players[player], &mut self.bag, self.melded[player], playouts, rng) {
+            Some(Turn::Play { board, placed }) => {
+                self.melded[player] = true;
+                self.board = board;
+                if self.players[player].tiles.is_empty() {
+                    return StepResult::Won { player };
+                }
+                StepResult::Played { player, placed }
+            }
+            Some(Turn::Draw(tile)) => StepResult::Drew { player, tile },
+            None => StepResult::Passed { player },
+        }
+    }
+}
+
+/// A move a player makes on their turn.
+pub enum Turn {
+    /// The player placed `placed` tiles on the board, consuming them from
+    /// their hand, leaving the board in the resulting state.
+    Play { board: Board, placed: Vec<Tile> },
+    /// No placement was possible (or worthwhile), so a tile was drawn from
+    /// the bag instead.
+    Draw(Tile),
+}
+
+/// Total value of a set of tiles, counting a joker as whatever it stands in
+/// for (or 0 if it's not yet placed and thus doesn't represent anything).
+fn tiles_value(tiles: &[Tile]) -> u32 {
+    tiles
+        .iter()
+        .map(|tile| tile.value().unwrap_or(0) as u32)
+        .sum()
+}
+
+/// Play a single turn for `player` against `board`.
+///
+/// Looks for the best placement of the player's hand via
+/// [`place_tiles_best`]. If `has_melded` is `false`, the placement is only
+/// considered once it is worth at least [`OPENING_MELD_MINIMUM`] points (the
+/// opening-meld rule); set `has_melded` to `true` to skip that check for
+/// players who have already made their first placement.
+///
+/// A placement clearing the opening-meld bar isn't taken unconditionally:
+/// [`monte_carlo_best_move`] compares it against doing nothing this turn, so
+/// a meld that would strand the hand in a worse shape (e.g. burning a joker
+/// the rest of the hand badly needed) is only made if it actually comes out
+/// ahead. When no placement exists, or playing it loses the comparison, a
+/// tile is drawn from `bag` instead.
+///
+/// # Returns
+/// * `Some(Turn)` - The move that was made
+/// * `None` - If no placement was possible and the bag was empty
+pub fn play_turn<R: Rng + ?Sized>(
+    board: &Board,
+    player: &mut Player,
+    bag: &mut Bag,
+    has_melded: bool,
+    playouts: u32,
+    rng: &mut R,
+) -> Option<Turn> {
+    if let Some((new_board, placed, unplaced)) = place_tiles_best(board, &player.tiles) {
+        if !placed.is_empty() && (has_melded || tiles_value(&placed) >= OPENING_MELD_MINIMUM) {
+            // Emptying the hand outright always wins the game; no need to
+            // spend playouts confirming it.
+            let plays_it_out = unplaced.is_empty();
+
+            let takes_it = plays_it_out || {
+                let unseen = unseen_tiles(board, &player.tiles);
+                let candidates = [(new_board.clone(), unplaced.clone()), (board.clone(), player.tiles.clone())];
+                monte_carlo_best_move(&candidates, &unseen, playouts, rng) == Some(0)
+            };
+
+            if takes_it {
+                player.tiles = unplaced;
+                return Some(Turn::Play {
+                    board: new_board,
+                    placed,
+                });
+            }
+        }
+    }
+
+    let drawn = bag.draw()?;
+    player.tiles.push(drawn);
+    Some(Turn::Draw(drawn))
+}
+
+/// Upper bound on how many simulated turns a single `average_turns_to_empty`
+/// playout runs before giving up. Each simulated turn re-runs the
+/// `solve_maximal` DP, so without a cap a playout against a large board
+/// could run for as long as the bag holds tiles; past this many turns the
+/// hand is scored as "stuck" on whatever tiles it has left, the same
+/// pessimistic treatment already used when the bag runs dry.
+const MAX_PLAYOUT_TURNS: u32 = 5;
+
+/// Monte Carlo estimate of how good a candidate placement is for emptying a
+/// hand first, compared to leaving the hand as-is.
+///
+/// Each playout draws random tiles in place of the ones no longer known
+/// (the remaining bag and the other players' hands, modeled as one shared
+/// pool of unseen tiles). Every drawn tile is immediately tried against
+/// [`place_tiles_best`] before moving to the next turn, the same greedy
+/// policy a perfect player would follow, so two hands of equal size but
+/// different tiles score differently based on how well each one actually
+/// plays against `board`, not just how many tiles are left.
+///
+/// # Arguments
+/// * `board` - The board this candidate's remaining hand would be played against
+/// * `remaining_hand` - The player's hand after the candidate placement
+/// * `unseen_tiles` - Every tile not on the board and not in the player's hand
+/// * `playouts` - How many random playouts to average over
+///
+/// # Returns
+/// The average number of turns until `remaining_hand` would empty, lower is better.
+fn average_turns_to_empty<R: Rng + ?Sized>(
+    board: &Board,
+    remaining_hand: &[Tile],
+    unseen_tiles: &[Tile],
+    playouts: u32,
+    rng: &mut R,
+) -> f64 {
+    if remaining_hand.is_empty() {
+        return 0.0;
+    }
+    // With no playouts to average over there's nothing to estimate; treat it
+    // as the worst possible score rather than dividing 0 by 0 into `NaN`.
+    if playouts == 0 {
+        return f64::INFINITY;
+    }
+
+    let mut total_turns = 0u64;
+    for _ in 0..playouts {
+        let mut pool = unseen_tiles.to_vec();
+        pool.shuffle(rng);
+
+        let mut playout_board = board.clone();
+        let mut hand = remaining_hand.to_vec();
+        let mut turns = 0u64;
+
+        for _ in 0..MAX_PLAYOUT_TURNS {
+            if hand.is_empty() {
+                break;
+            }
+            match place_tiles_best(&playout_board, &hand) {
+                Some((new_board, _, unplaced)) => {
+                    playout_board = new_board;
+                    hand = unplaced;
+                    turns += 1;
+                }
+                None => match pool.pop() {
+                    Some(tile) => {
+                        hand.push(tile);
+                        turns += 1;
+                    }
+                    // Nothing left to draw and nothing placeable: this hand
+                    // never empties in this playout.
+                    None => break,
+                },
+            }
+        }
+
+        turns += hand.len() as u64; // tiles that could never be placed or drawn back within the cap
+        total_turns += turns;
+    }
+
+    total_turns as f64 / playouts as f64
+}
+
+/// Pick the best of several candidate placements via Monte Carlo playouts.
+///
+/// `candidates` pairs each candidate's resulting board with the hand it
+/// would leave behind. Returns the index of the candidate with the lowest
+/// average turns-to-empty, i.e. the one most likely to empty the player's
+/// hand first.
+///
+/// # Returns
+/// * `Some(index)` - The index into `candidates` of the best move
+/// * `None` - If `candidates` is empty
+pub fn monte_carlo_best_move<R: Rng + ?Sized>(
+    candidates: &[(Board, Vec<Tile>)],
+    unseen_tiles: &[Tile],
+    playouts: u32,
+    rng: &mut R,
+) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(index, (board, remaining_hand))| {
+            let score = average_turns_to_empty(board, remaining_hand, unseen_tiles, playouts, rng);
+            (index, score)
+        })
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::Run;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(1)
+    }
+
+    #[test]
+    fn bag_new_holds_a_full_standard_set() {
+        let bag = Bag::new(&mut rng());
+        assert_eq!(bag.len(), 106);
+    }
+
+    #[test]
+    fn deal_hand_returns_none_when_bag_is_too_small() {
+        let mut bag = Bag { tiles: vec![Tile::Numbered { color: Color::Blue, value: 1 }] };
+        assert!(bag.deal_hand().is_none());
+    }
+
+    #[test]
+    fn unseen_tiles_excludes_board_and_hand_tiles() {
+        let board = Board {
+            groups: vec![],
+            runs: vec![Run { start: 1, end: 3, color: Color::Blue, jokers: Default::default() }],
+        };
+        let hand = vec![Tile::Numbered { color: Color::Red, value: 5 }];
+
+        let pool = unseen_tiles(&board, &hand);
+
+        assert_eq!(pool.len(), 106 - 3 - 1);
+        // Each color/value has two physical copies, so removing one still
+        // leaves the other in the unseen pool.
+        assert_eq!(pool.iter().filter(|t| **t == Tile::Numbered { color: Color::Red, value: 5 }).count(), 1);
+        assert_eq!(pool.iter().filter(|t| **t == Tile::Numbered { color: Color::Blue, value: 1 }).count(), 1);
+    }
+
+    #[test]
+    fn average_turns_to_empty_favors_a_hand_that_can_actually_place() {
+        let board = Board {
+            groups: vec![],
+            runs: vec![Run { start: 1, end: 3, color: Color::Blue, jokers: Default::default() }],
+        };
+        // One drawable tile, so a hand that never places still racks up a
+        // turn drawing it before getting stuck, rather than giving up instantly.
+        let unseen = vec![Tile::Numbered { color: Color::Black, value: 12 }];
+
+        // This hand extends the existing run in one move.
+        let placeable_hand = vec![Tile::Numbered { color: Color::Blue, value: 4 }];
+        // This hand shares nothing with the board and can never be placed.
+        let stuck_hand = vec![Tile::Numbered { color: Color::Red, value: 9 }];
+
+        let placeable_score = average_turns_to_empty(&board, &placeable_hand, &unseen, 10, &mut rng());
+        let stuck_score = average_turns_to_empty(&board, &stuck_hand, &unseen, 10, &mut rng());
+
+        assert!(placeable_score < stuck_score);
+    }
+
+    #[test]
+    fn monte_carlo_best_move_picks_the_playable_candidate() {
+        let board = Board {
+            groups: vec![],
+            runs: vec![Run { start: 1, end: 3, color: Color::Blue, jokers: Default::default() }],
+        };
+        let candidates = [
+            (board.clone(), vec![Tile::Numbered { color: Color::Blue, value: 4 }]),
+            (board.clone(), vec![Tile::Numbered { color: Color::Red, value: 9 }]),
+        ];
+
+        let best = monte_carlo_best_move(&candidates, &[], 10, &mut rng());
+
+        assert_eq!(best, Some(0));
+    }
+
+    #[test]
+    fn monte_carlo_best_move_does_not_panic_with_zero_playouts() {
+        let board = Board {
+            groups: vec![],
+            runs: vec![Run { start: 1, end: 3, color: Color::Blue, jokers: Default::default() }],
+        };
+        let candidates = [
+            (board.clone(), vec![Tile::Numbered { color: Color::Blue, value: 4 }]),
+            (board.clone(), vec![Tile::Numbered { color: Color::Red, value: 9 }]),
+        ];
+
+        // With no playouts to average over, every non-empty hand scores the
+        // same (infinite); this must return a defined index rather than
+        // panicking on a `NaN` comparison.
+        let best = monte_carlo_best_move(&candidates, &[], 0, &mut rng());
+
+        assert_eq!(best, Some(0));
+    }
+
+    #[test]
+    fn play_turn_draws_when_no_meld_reaches_the_opening_minimum() {
+        let board = Board { groups: vec![], runs: vec![] };
+        let mut player = Player {
+            tiles: vec![
+                Tile::Numbered { color: Color::Blue, value: 1 },
+                Tile::Numbered { color: Color::Blue, value: 2 },
+                Tile::Numbered { color: Color::Blue, value: 3 },
+            ],
+        };
+        let mut bag = Bag { tiles: vec![Tile::Numbered { color: Color::Red, value: 8 }] };
+
+        let turn = play_turn(&board, &mut player, &mut bag, false, 4, &mut rng());
+
+        assert!(matches!(turn, Some(Turn::Draw(_))));
+        assert_eq!(player.tiles.len(), 4);
+    }
+
+    #[test]
+    fn play_turn_melds_once_it_clears_the_opening_minimum() {
+        let board = Board { groups: vec![], runs: vec![] };
+        let mut player = Player {
+            tiles: vec![
+                Tile::Numbered { color: Color::Blue, value: 11 },
+                Tile::Numbered { color: Color::Blue, value: 12 },
+                Tile::Numbered { color: Color::Blue, value: 13 },
+            ],
+        };
+        let mut bag = Bag { tiles: vec![] };
+
+        let turn = play_turn(&board, &mut player, &mut bag, false, 4, &mut rng());
+
+        assert!(matches!(turn, Some(Turn::Play { .. })));
+        assert!(player.tiles.is_empty());
+    }
+
+    #[test]
+    fn game_new_deals_a_separate_hand_to_every_player() {
+        let game = Game::new(3, &mut rng()).unwrap();
+
+        assert_eq!(game.players.len(), 3);
+        for player in &game.players {
+            assert_eq!(player.tiles.len(), HAND_SIZE);
+        }
+        // No tile should be shared between hands or left on the board.
+        let mut seen = board_tiles(&game.board);
+        for player in &game.players {
+            seen.extend(player.tiles.iter().copied());
+        }
+        assert_eq!(seen.len(), 3 * HAND_SIZE);
+    }
+
+    #[test]
+    fn game_step_rotates_through_every_seat_in_order() {
+        let mut game = Game::new(3, &mut rng()).unwrap();
+
+        let mut seats_seen = vec![];
+        for _ in 0..6 {
+            match game.step(0, &mut rng()) {
+                StepResult::Played { player, .. } => seats_seen.push(player),
+                StepResult::Drew { player, .. } => seats_seen.push(player),
+                StepResult::Passed { player } => seats_seen.push(player),
+                StepResult::Won { player } => seats_seen.push(player),
+            }
+        }
+
+        assert_eq!(seats_seen, vec![0, 1, 2, 0, 1, 2]);
+    }
+}
@@ -1,32 +1,79 @@
+use crate::game::{Game, StepResult, DEFAULT_PLAYER_COUNT, DEFAULT_PLAYOUTS};
 use crate::mutations::place_tiles_best;
-use crate::structures::{Board, Run};
-use crate::structures::{Color, Tile};
+use crate::notation::{parse_board, parse_hand};
+use clap::Parser;
+use std::fs;
 
+mod game;
 mod mutations;
+mod notation;
 pub mod structures;
 
+/// Compute the best placement of a hand of tiles against a board, or play
+/// out a full multi-player game with [`simulate`].
+///
+/// Board and hand can each be given directly as notation strings, or loaded
+/// from a file (one takes precedence over the other if both are given).
+/// Board notation is whitespace-separated `run:color:start-end` and
+/// `group:value:color,color,color` tokens; hand notation is
+/// whitespace-separated tile tokens like `b3`, `blue4`, or `j` for a joker.
+#[derive(Parser)]
+struct Args {
+    /// Board notation, e.g. "run:blue:2-5 group:10:orange,blue,red".
+    #[arg(long)]
+    board: Option<String>,
+
+    /// Path to a file containing board notation.
+    #[arg(long)]
+    board_file: Option<String>,
+
+    /// Hand notation, e.g. "b3 b4 k1 b2".
+    #[arg(long, default_value = "b3 b4 k1 b2")]
+    hand: Option<String>,
+
+    /// Path to a file containing hand notation.
+    #[arg(long)]
+    hand_file: Option<String>,
+
+    /// Instead of solving a single placement, deal fresh hands from a
+    /// shuffled bag and play a full multi-player game against an empty
+    /// board, turn by turn, via the Monte Carlo opponent in `game::Game`.
+    #[arg(long)]
+    simulate: bool,
+
+    /// Number of players dealt into the simulated game (only used with
+    /// `--simulate`).
+    #[arg(long, default_value_t = DEFAULT_PLAYER_COUNT)]
+    players: usize,
+}
+
 fn main() {
-    let mut board = test_board();
-    println!("{}", board.to_string());
-
-    let tiles = vec![
-        Tile {
-            color: Color::Blue,
-            value: 3,
-        },
-        Tile {
-            color: Color::Blue,
-            value: 4,
-        },
-        Tile {
-            color: Color::Black,
-            value: 1,
-        },
-        Tile {
-            color: Color::Blue,
-            value: 2,
-        },
-    ];
+    let args = Args::parse();
+
+    if args.simulate {
+        simulate(args.players);
+        return;
+    }
+
+    let board_notation = match args.board_file {
+        Some(path) => read_notation_file(&path),
+        None => args.board.unwrap_or_else(|| "run:blue:2-5 run:blue:6-13".to_string()),
+    };
+    let hand_notation = match args.hand_file {
+        Some(path) => read_notation_file(&path),
+        None => args.hand.unwrap_or_default(),
+    };
+
+    let board = parse_board(&board_notation).unwrap_or_else(|err| {
+        eprintln!("invalid --board: {}", err);
+        std::process::exit(1);
+    });
+    let tiles = parse_hand(&hand_notation).unwrap_or_else(|err| {
+        eprintln!("invalid --hand: {}", err);
+        std::process::exit(1);
+    });
+
+    println!("{}", board);
 
     match place_tiles_best(&board, &tiles) {
         Some((new_board, placed_tiles, unplaced_tiles)) => {
@@ -47,10 +94,7 @@ fn main() {
                     .collect::<Vec<_>>()
                     .join(" ")
             );
-            println!("before:\n{}", board.to_string());
-            board = new_board;
-
-            println!("after:\n{}", board.to_string());
+            println!("after:\n{}", new_board);
         }
         None => {
             println!("no solution found");
@@ -58,32 +102,63 @@ fn main() {
     }
 }
 
-fn test_board() -> Board {
-    let mut board = Board {
-        groups: vec![],
-        runs: vec![],
-    };
-
-    board.runs.push(Run {
-        start: 2,
-        end: 5,
-        color: Color::Blue,
+/// Deal `num_players` hands from a shuffled bag and play a full game against
+/// an empty board, one turn at a time in seat order, until someone empties
+/// their hand or the bag runs dry for everyone.
+fn simulate(num_players: usize) {
+    let mut rng = rand::thread_rng();
+    let mut game = Game::new(num_players, &mut rng).unwrap_or_else(|| {
+        eprintln!("bag doesn't hold enough tiles to deal {} players a starting hand", num_players);
+        std::process::exit(1);
     });
 
-    board.runs.push(Run {
-        start: 6,
-        end: 13,
-        color: Color::Blue,
-    });
+    let mut turn_number = 0u32;
+    let mut passes_in_a_row = 0usize;
+
+    loop {
+        match game.step(DEFAULT_PLAYOUTS, &mut rng) {
+            StepResult::Played { player, placed, .. } => {
+                passes_in_a_row = 0;
+                println!(
+                    "Turn {} (player {}): played {}",
+                    turn_number,
+                    player,
+                    placed.iter().map(|t| format!("{}", t)).collect::<Vec<_>>().join(" ")
+                );
+            }
+            StepResult::Drew { player, tile } => {
+                passes_in_a_row = 0;
+                println!(
+                    "Turn {} (player {}): drew {} ({} left in bag)",
+                    turn_number,
+                    player,
+                    tile,
+                    game.tiles_left()
+                );
+            }
+            StepResult::Passed { player } => {
+                println!("Turn {} (player {}): bag is empty, nothing to play", turn_number, player);
+                passes_in_a_row += 1;
+                if passes_in_a_row >= num_players {
+                    println!("Every player is stuck with an empty bag; ending the game");
+                    break;
+                }
+            }
+            StepResult::Won { player } => {
+                println!("Turn {} (player {}): emptied their hand and won!", turn_number, player);
+                break;
+            }
+        }
+
+        turn_number += 1;
+    }
+
+    println!("{}", game.board);
+}
 
-    // board.runs.push(Run {
-    //     start: 1,
-    //     end: 3,
-    //     color: Color::Orange,
-    // });
-    // board.groups.push(Group {
-    //     value: 10,
-    //     colors: vec![Color::Orange, Color::Blue, Color::Red],
-    // });
-    return board;
+fn read_notation_file(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("could not read '{}': {}", path, err);
+        std::process::exit(1);
+    })
 }
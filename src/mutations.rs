@@ -1,384 +1,779 @@
-use crate::structures::{Board, Run, Tile};
+use crate::structures::{Board, Color, Group, Run, Tile};
 use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
 
-/// Extend a run by adding a tile at the end.
+/// Find the optimal tile placement via the `solve_maximal` DP.
 ///
-/// Attempts to add a tile to the end of an existing run. The tile must:
-/// - Have the same color as the run
-/// - Have a value that is exactly one greater than the run's end value
+/// Delegates to [`solve_maximal`], which re-decomposes the whole board
+/// together with `tiles` to find the decomposition that plays the most hand
+/// tiles while keeping every existing board tile somewhere on the board.
+/// That DP is polynomial in the number of distinct (color, value) slots
+/// (there are only 13 values and 4 colors), unlike trying every ordering of
+/// `tiles` by hand, which is factorial in the hand size.
+///
+/// Since `solve_maximal` only returns the resulting board, this diffs it
+/// against the board's own tiles to recover which of `tiles` actually got
+/// used, by (color, value) rather than identity: however many more copies
+/// of a given numbered tile (or joker) ended up on the new board than were
+/// already mandatory from the old one must have come from the hand.
 ///
 /// # Arguments
-/// * `run` - The existing run to extend
-/// * `tile` - The tile to add at the end
+/// * `board` - The current game board
+/// * `tiles` - Slice of tiles to place optimally
 ///
 /// # Returns
-/// * `Some(Run)` - A new run with the tile added if extension is valid
-/// * `None` - If the tile cannot be added (wrong color or non-consecutive value)
+/// * `Option<(Board, Vec<Tile>, Vec<Tile>)>` - Option containing:
+///   - The board after optimal tile placement
+///   - Vector of tiles that were successfully placed
+///   - Vector of tiles that could not be placed
+/// * `None` - If no hand tile could be placed at all
 ///
 /// # Example
 /// ```
-/// // Run: Blue 3-4-5 (end=5), Tile: Blue 6
-/// // Returns: Some(Run { start: 3, end: 6, color: Blue })
+/// // Given tiles [Blue 6, Orange 4, Blue 1] and a board with runs Blue 2-5, Orange 1-3
+/// // Might find Blue 1, Blue 6, Orange 4 places optimally
+/// // Returns (updated_board, [Blue 1, Blue 6, Orange 4], [])
 /// ```
-fn extend_run_tail(run: &Run, tile: &Tile) -> Option<Run> {
-    if run.color == tile.color && run.end == tile.value - 1 {
-        let new_run = Some(Run {
-            start: run.start,
-            end: tile.value,
-            color: run.color,
-        });
-        new_run
-    } else {
-        None
+pub fn place_tiles_best(board: &Board, tiles: &[Tile]) -> Option<(Board, Vec<Tile>, Vec<Tile>)> {
+    let new_board = solve_maximal(board, tiles)?;
+    let pool = gather_pool(board, tiles);
+
+    let mut used_counts: HashMap<(Color, u8), u8> = HashMap::new();
+    let mut used_jokers = 0u8;
+    for tile in board_tiles(&new_board) {
+        match tile {
+            Tile::Numbered { color, value } => *used_counts.entry((color, value)).or_insert(0) += 1,
+            Tile::Joker { .. } => used_jokers += 1,
+        }
     }
-}
 
-/// Extend a run by adding a tile at the beginning.
-///
-/// Attempts to add a tile to the beginning of an existing run. The tile must:
-/// - Have the same color as the run
-/// - Have a value that is exactly one less than the run's start value
-///
-/// # Arguments
-/// * `run` - The existing run to extend
-/// * `tile` - The tile to add at the beginning
-///
-/// # Returns
-/// * `Some(Run)` - A new run with the tile added if extension is valid
-/// * `None` - If the tile cannot be added (wrong color or non-consecutive value)
-///
-/// # Example
-/// ```
-/// // Run: Blue 3-4-5 (start=3), Tile: Blue 2
-/// // Returns: Some(Run { start: 2, end: 5, color: Blue })
-/// ```
-fn extend_run_head(run: &Run, tile: &Tile) -> Option<Run> {
-    if run.color == tile.color && run.start == tile.value + 1 {
-        let new_run = Some(Run {
-            start: tile.value,
-            end: run.end,
-            color: run.color,
-        });
-        new_run
-    } else {
-        None
+    let mut remaining_hand = tiles.to_vec();
+    let mut placed_tiles = vec![];
+
+    for (key, used) in used_counts {
+        let mandatory = *pool.mandatory_counts.get(&key).unwrap_or(&0);
+        for _ in 0..used.saturating_sub(mandatory) {
+            let position = remaining_hand
+                .iter()
+                .position(|tile| tile.color() == Some(key.0) && tile.value() == Some(key.1) && !tile.is_joker());
+            if let Some(position) = position {
+                placed_tiles.push(remaining_hand.remove(position));
+            }
+        }
+    }
+
+    for _ in 0..used_jokers.saturating_sub(pool.mandatory_jokers) {
+        if let Some(position) = remaining_hand.iter().position(Tile::is_joker) {
+            placed_tiles.push(remaining_hand.remove(position));
+        }
     }
+
+    if placed_tiles.is_empty() {
+        return None;
+    }
+
+    Some((new_board, placed_tiles, remaining_hand))
 }
 
-/// Generate all possible ways to split a run into multiple runs.
-///
-/// Returns all possible ways to break a run into separate, non-overlapping runs.
-/// For a run 1-3, this returns: [[1], [2,3]], [[1,2], [3]], [[1], [2], [3]].
-///
-/// # Arguments
-/// * `run` - The run to split into multiple runs
-///
-/// # Returns
-/// * `Vec<Vec<Run>>` - All possible ways to split the run, where each inner Vec
-///   contains the runs for that particular split
-///
-/// # Example
-/// ```
-/// // Run: Blue 1-3 (values 1, 2, 3)
-/// // Returns: [
-/// //   [Run(1,1), Run(2,3)],  // Split after 1
-/// //   [Run(1,2), Run(3,3)],  // Split after 2  
-/// //   [Run(1,1), Run(2,2), Run(3,3)]  // Split after 1 and 2
-/// // ]
-/// ```
-fn split_run(run: &Run) -> Vec<Vec<Run>> {
-    let mut all_splits = Vec::new();
-    let run_length = run.end - run.start + 1;
-
-    // Generate all possible split points (between tiles)
-    // For run 1-3, split points are after 1 and after 2
-    for split_mask in 1..(1 << (run_length - 1)) {
-        let mut current_split = Vec::new();
-        let mut current_start = run.start;
-
-        for i in 0..(run_length - 1) {
-            if (split_mask & (1 << i)) != 0 {
-                // There's a split after position current_start + i
-                let current_end = run.start + i;
-                current_split.push(Run {
-                    start: current_start,
-                    end: current_end,
+/// Every physical tile found in the board's runs and groups, expanding
+/// jokers back into generic wildcard pieces (their `standing_in` no longer
+/// matters once the whole board is being re-pooled).
+pub(crate) fn board_tiles(board: &Board) -> Vec<Tile> {
+    let mut tiles = vec![];
+
+    for run in &board.runs {
+        for value in run.start..=run.end {
+            if run.jokers.contains(&value) {
+                tiles.push(Tile::Joker { standing_in: None });
+            } else {
+                tiles.push(Tile::Numbered {
                     color: run.color,
+                    value,
                 });
-                current_start = current_end + 1;
             }
         }
+    }
 
-        // Add the final segment
-        current_split.push(Run {
-            start: current_start,
-            end: run.end,
-            color: run.color,
-        });
-
-        all_splits.push(current_split);
+    for group in &board.groups {
+        for color in &group.colors {
+            if group.jokers.contains(color) {
+                tiles.push(Tile::Joker { standing_in: None });
+            } else {
+                tiles.push(Tile::Numbered {
+                    color: *color,
+                    value: group.value,
+                });
+            }
+        }
     }
 
-    all_splits
+    tiles
 }
 
-/// Compress adjacent runs of the same color into larger runs.
-///
-/// Takes a collection of runs and merges any that are adjacent and of the same color.
-/// For example, [Blue 1-2, Blue 3-4] becomes [Blue 1-4].
-///
-/// # Arguments
-/// * `runs` - Slice of runs to compress
-///
-/// # Returns
-/// * `Vec<Run>` - Compressed runs with adjacent runs merged
-///
-/// # Example
-/// ```
-/// // Input: [Blue 1-2, Blue 3-4, Red 5-6]
-/// // Output: [Blue 1-4, Red 5-6]
-/// ```
-fn compress_runs(runs: &[Run]) -> Vec<Run> {
-    if runs.is_empty() {
-        return vec![];
-    }
+/// How many copies of every numbered tile and how many jokers are available
+/// to `solve_maximal`, split into what came from the board (must be used
+/// somewhere in the result) and what's merely on offer from the hand.
+struct TilePool {
+    /// Total copies (board + hand) of each (color, value), 0..=2.
+    counts: HashMap<(Color, u8), u8>,
+    /// Of `counts`, how many copies came from the board.
+    mandatory_counts: HashMap<(Color, u8), u8>,
+    /// Total joker pieces (board + hand), usually 0..=2.
+    jokers: u8,
+    /// Of `jokers`, how many came from the board.
+    mandatory_jokers: u8,
+}
 
-    let mut compressed = Vec::new();
-    let mut sorted_runs = runs.to_vec();
-
-    // Sort runs by color first, then by start position
-    sorted_runs.sort_by(|a, b| match a.color as u8 {
-        ac => match b.color as u8 {
-            bc => {
-                if ac == bc {
-                    a.start.cmp(&b.start)
-                } else {
-                    ac.cmp(&bc)
-                }
+fn gather_pool(board: &Board, hand: &[Tile]) -> TilePool {
+    let mut pool = TilePool {
+        counts: HashMap::new(),
+        mandatory_counts: HashMap::new(),
+        jokers: 0,
+        mandatory_jokers: 0,
+    };
+
+    let record = |tile: &Tile, is_board: bool, pool: &mut TilePool| match tile {
+        Tile::Numbered { color, value } => {
+            *pool.counts.entry((*color, *value)).or_insert(0) += 1;
+            if is_board {
+                *pool.mandatory_counts.entry((*color, *value)).or_insert(0) += 1;
             }
-        },
-    });
-
-    let mut current_run = sorted_runs[0].clone();
-
-    for run in sorted_runs.iter().skip(1) {
-        // Check if this run can be merged with the current run
-        if run.color == current_run.color && run.start == current_run.end + 1 {
-            // Extend the current run
-            current_run.end = run.end;
-        } else {
-            // Can't merge, add current run to result and start a new one
-            compressed.push(current_run);
-            current_run = run.clone();
         }
+        Tile::Joker { .. } => {
+            pool.jokers += 1;
+            if is_board {
+                pool.mandatory_jokers += 1;
+            }
+        }
+    };
+
+    for tile in board_tiles(board) {
+        record(&tile, true, &mut pool);
+    }
+    for tile in hand {
+        record(tile, false, &mut pool);
     }
 
-    // Don't forget the last run
-    compressed.push(current_run);
+    pool
+}
 
-    compressed
+/// One run of a given color that's still open at the DP frontier, i.e. not
+/// yet decided to be finalized. Its length at a boundary before value `v` is
+/// `v - start`, which is all that's needed to tell whether it still needs
+/// (at least) one more tile before it's a valid run.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+struct OpenRun {
+    start: u8,
+    /// Values in `start..v` that this run has been extended with a joker
+    /// rather than a real tile, in increasing order. Carried along so a
+    /// closed run can still report exactly which of its slots are jokers.
+    jokers: Vec<u8>,
 }
 
-/// Extend a run by adding a tile at either end.
-///
-/// Attempts to extend a run by first trying to add the tile at the beginning,
-/// and if that fails, trying to add it at the end.
-///
-/// # Arguments
-/// * `run` - The existing run to extend
-/// * `tile` - The tile to add
-///
-/// # Returns
-/// * `Some(Run)` - A new run with the tile added if extension is possible
-/// * `None` - If the tile cannot be added to either end
-///
-/// # Example
-/// ```
-/// // Run: Blue 3-4-5, Tile: Blue 2 -> extends at head
-/// // Run: Blue 3-4-5, Tile: Blue 6 -> extends at tail
-/// // Run: Blue 3-4-5, Tile: Blue 8 -> returns None
-/// ```
-fn extend_run(run: &Run, tile: &Tile) -> Option<Run> {
-    match extend_run_head(run, tile) {
-        Some(run) => Some(run),
-        None => extend_run_tail(run, tile),
-    }
+/// The full DP state at a value boundary: which runs are still open per
+/// color (at most two per color, since a color only has two physical copies
+/// of any boundary value), and how many jokers are still unspent.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+struct DpState {
+    opens: [Vec<OpenRun>; 4],
+    jokers_remaining: u8,
 }
 
-/// Attempt to place a single tile on the board.
-///
-/// Currently only tries to extend the first run on the board. The tile must
-/// be able to extend the run at either the beginning or end.
-///
-/// # Arguments
-/// * `board` - The current game board
-/// * `tile` - The tile to place
-///
-/// # Returns
-/// * `Some(Board)` - A new board with the tile placed if successful
-/// * `None` - If the tile cannot be placed
-///
-pub fn place_tile(board: &Board, tile: &Tile) -> Option<Board> {
-    let mut new_board = board.clone();
-
-    // Try to extend each run on the board
-    for (index, run) in new_board.runs.iter().enumerate() {
-        if let Some(extended_run) = extend_run(run, tile) {
-            new_board.runs[index] = extended_run;
-            println!("placed {}", tile);
-            println!("{}", new_board.to_string());
-            return Some(new_board);
+/// One way a single color's open runs can evolve across the transition at
+/// value `v`, using only the color's own `real_left_optional` tiles (tiles
+/// needed to keep already-open length-1/length-2 runs alive are handled by
+/// the caller beforehand, since that part isn't a choice).
+struct ColorTransition {
+    next_opens: Vec<OpenRun>,
+    /// Tiles spent continuing a long-open run or starting a new one.
+    optional_consumed: u8,
+    /// Tiles left over after that, which may still go into a group this round.
+    leftover_real: u8,
+    /// Runs that got finalized (closed) by this choice.
+    closed_runs: Vec<Run>,
+}
+
+/// Enumerate what a color can do with its open runs at value `v`, given that
+/// `real_left_optional` tiles of (color, v) remain after covering the
+/// mandatory length-1/length-2 continuations, of which `joker_shortfall` of
+/// those mandatory continuations have to be a joker rather than a real tile
+/// (the caller already worked out how many; which particular open gets the
+/// joker doesn't affect feasibility or score, so we just pick the last
+/// `joker_shortfall` of them).
+fn color_transitions(opens: &[OpenRun], v: u8, color: Color, real_left_optional: u8, joker_shortfall: u8) -> Vec<ColorTransition> {
+    let mut stage1 = vec![]; // length 1, must extend
+    let mut stage2 = vec![]; // length 2, must extend
+    let mut stage3 = vec![]; // length >= 3, already valid, may extend or close
+    for open in opens {
+        match v - open.start {
+            1 => stage1.push(open.clone()),
+            2 => stage2.push(open.clone()),
+            _ => stage3.push(open.clone()),
         }
     }
 
-    // If no extension worked, try splitting runs and placing tile in the splits
-    for (run_index, run) in board.runs.iter().enumerate() {
-        let all_splits = split_run(run);
-
-        for split_combination in all_splits {
-            // Try to extend each run in this split combination with the tile
-            for (split_index, split_run) in split_combination.iter().enumerate() {
-                if let Some(extended_split) = extend_run(split_run, tile) {
-                    // Create a new board with this split combination, replacing the original run
-                    let mut split_board = board.clone();
-
-                    // Remove the original run
-                    split_board.runs.remove(run_index);
-
-                    // Add all the split runs, with one extended
-                    for (i, split_run_copy) in split_combination.iter().enumerate() {
-                        if i == split_index {
-                            split_board
-                                .runs
-                                .insert(run_index + i, extended_split.clone());
-                        } else {
-                            split_board
-                                .runs
-                                .insert(run_index + i, split_run_copy.clone());
-                        }
-                    }
-                    println!("split and placed {}", tile);
-                    println!("{}", split_board.to_string());
-                    return Some(split_board);
-                }
+    let carried_open_count = stage1.len() + stage2.len();
+    let mut mandatory_extended: Vec<OpenRun> = stage1.iter().chain(stage2.iter()).cloned().collect();
+    let joker_shortfall = joker_shortfall as usize;
+    for open in mandatory_extended.iter_mut().rev().take(joker_shortfall) {
+        open.jokers.push(v);
+    }
+    let mut results = vec![];
+
+    for continue3 in 0..=stage3.len() {
+        if continue3 as u8 > real_left_optional {
+            continue;
+        }
+        let real_after_continue3 = real_left_optional - continue3 as u8;
+
+        let max_new = (2usize.saturating_sub(carried_open_count + continue3)).min(real_after_continue3 as usize);
+        for new_runs in 0..=max_new {
+            let leftover_real = real_after_continue3 - new_runs as u8;
+
+            let mut next_opens: Vec<OpenRun> = mandatory_extended
+                .iter()
+                .chain(stage3.iter().take(continue3))
+                .cloned()
+                .collect();
+
+            let closed_runs = stage3
+                .iter()
+                .skip(continue3)
+                .map(|closed| Run {
+                    start: closed.start,
+                    end: v - 1,
+                    color,
+                    jokers: closed.jokers.iter().copied().collect(),
+                })
+                .collect();
+
+            for _ in 0..new_runs {
+                next_opens.push(OpenRun { start: v, jokers: vec![] });
             }
+            next_opens.sort_by_key(|open| open.start);
+
+            results.push(ColorTransition {
+                next_opens,
+                optional_consumed: continue3 as u8 + new_runs as u8,
+                leftover_real,
+                closed_runs,
+            });
         }
     }
 
-    None
+    results
 }
 
-/// Recursively place multiple tiles on the board.
-///
-/// Attempts to place tiles one by one in the given order. For each tile that
-/// can be placed, it recursively tries to place the remaining tiles on the
-/// updated board. This ensures maximum tile placement.
-///
-/// The result is the modified board, and the list of tiles that are placed.
-///
-/// # Arguments
-/// * `board` - The current game board
-/// * `tiles` - Vector of tile references to place
-///
-/// # Returns
-/// * `(Board, Vec<&Tile>)` - Tuple containing:
-///   - The updated board after placing tiles
-///   - Vector of tiles that were successfully placed
-///
-/// # Example
-/// ```
-/// // Given tiles [Blue 6, Blue 7, Red 5] and a board with Blue 4-5 run
-/// // Might place Blue 6, Blue 7 and return (updated_board, [Blue 6, Blue 7])
-/// ```
-pub fn place_tiles(board: &Board, tiles: &[Tile]) -> (Board, Vec<Tile>) {
-    let mut modified_board = board.clone();
-    let mut placed_tiles: Vec<Tile> = vec![];
+/// Every way to form a single brand-new group of value `value` out of the
+/// colors that still have a leftover real tile this round, optionally
+/// filling one missing color with a joker (if one is still unspent).
+///
+/// Returns `(group, consumed_per_color, jokers_used)` triples.
+fn single_group_options(leftover: &HashMap<Color, u8>, jokers_left: u8, value: u8) -> Vec<(Group, HashMap<Color, u8>, u8)> {
+    let mut options = vec![];
+
+    let colors_with_real: Vec<Color> = Color::ALL
+        .iter()
+        .copied()
+        .filter(|color| *leftover.get(color).unwrap_or(&0) > 0)
+        .collect();
+
+    for size in [4usize, 3usize] {
+        if colors_with_real.len() >= size {
+            for combo in colors_with_real.iter().combinations(size) {
+                let mut colors: Vec<Color> = combo.into_iter().copied().collect();
+                colors.sort_by_key(|color| *color as u8);
+                let consumed = colors.iter().map(|color| (*color, 1u8)).collect();
+                options.push((
+                    Group {
+                        value,
+                        colors,
+                        jokers: HashSet::new(),
+                    },
+                    consumed,
+                    0,
+                ));
+            }
+        }
+
+        if jokers_left >= 1 && colors_with_real.len() >= size - 1 {
+            for combo in colors_with_real.iter().combinations(size - 1) {
+                let used: HashSet<Color> = combo.iter().map(|color| **color).collect();
+                for missing in Color::ALL.iter().copied().filter(|color| !used.contains(color)) {
+                    let mut colors: Vec<Color> = combo.iter().map(|color| **color).collect();
+                    colors.push(missing);
+                    colors.sort_by_key(|color| *color as u8);
+                    let consumed = used.iter().map(|color| (*color, 1u8)).collect();
+                    let mut jokers = HashSet::new();
+                    jokers.insert(missing);
+                    options.push((Group { value, colors, jokers }, consumed, 1));
+                }
+            }
+        }
+    }
 
-    for (index, tile) in tiles.iter().enumerate() {
-        // try and place the tile, and if it succeeds, move on to the next
-        match place_tile(&modified_board, tile) {
-            Some(new_board) => {
-                let remaining_tiles = &tiles[index + 1..];
+    options
+}
 
-                // add the tile we just placed
-                placed_tiles.push(*tile);
+/// Every way to form brand-new groups of value `value` out of the colors
+/// that still have a leftover real tile this round, optionally filling a
+/// missing color with a joker.
+///
+/// More than one same-value group can legally form in the same round: e.g.
+/// `{Blue, Red, Orange}` and `{Blue, Red, Black}` both at value 1 are two
+/// disjoint-enough groups since Blue and Red each have two physical copies
+/// of value 1 to spend. Since there are only 4 colors and a group needs at
+/// least 3 of them, no value can ever support more than two new groups at
+/// once (three size->=3 groups would need at least 9 color-slots, but two
+/// copies of 4 colors only supply 8), so this only ever considers 0, 1, or 2
+/// groups.
+///
+/// Returns `(groups, consumed_per_color, jokers_used)` triples; `groups` is
+/// empty for the "don't form a group this round" option.
+fn group_options(leftover: &HashMap<Color, u8>, jokers_left: u8, value: u8) -> Vec<(Vec<Group>, HashMap<Color, u8>, u8)> {
+    let singles = single_group_options(leftover, jokers_left, value);
+
+    let mut options = vec![(vec![], HashMap::new(), 0)];
+    for (group, consumed, jokers_used) in &singles {
+        options.push((vec![group.clone()], consumed.clone(), *jokers_used));
+    }
 
-                let (final_board, mut sub_placed_tiles) = place_tiles(&new_board, remaining_tiles);
+    for i in 0..singles.len() {
+        for j in (i + 1)..singles.len() {
+            let (group_a, consumed_a, jokers_a) = &singles[i];
+            let (group_b, consumed_b, jokers_b) = &singles[j];
 
-                placed_tiles.append(&mut sub_placed_tiles);
-                modified_board = final_board;
-                break; // Found a placement, continue with remaining tiles
+            let combined_jokers = jokers_a + jokers_b;
+            if combined_jokers > jokers_left {
+                continue;
             }
-            None => {
-                // Continue to next tile if this one can't be placed
+
+            let mut combined_consumed = consumed_a.clone();
+            let mut fits = true;
+            for (color, count) in consumed_b {
+                let total = *combined_consumed.get(color).unwrap_or(&0) + count;
+                if total > *leftover.get(color).unwrap_or(&0) {
+                    fits = false;
+                    break;
+                }
+                combined_consumed.insert(*color, total);
             }
+            if !fits {
+                continue;
+            }
+
+            options.push((vec![group_a.clone(), group_b.clone()], combined_consumed, combined_jokers));
         }
     }
-    (modified_board, placed_tiles)
+
+    options
 }
 
-/// Find the optimal tile placement by trying all permutations.
-///
-/// Attempts to place tiles in all possible orders and returns the arrangement
-/// that places the maximum number of tiles. This brute-force approach ensures
-/// the best possible outcome but can be expensive for large tile sets.
-///
-/// # Arguments
-/// * `board` - The current game board
-/// * `tiles` - Slice of tiles to place optimally
+/// Re-decompose the whole table: pool every tile on the board together with
+/// the hand, and partition it into valid runs and groups that maximize the
+/// number of hand tiles used, while guaranteeing every tile already on the
+/// board still appears somewhere in the result.
+///
+/// This implements the Den Hertog-Hulshof style dynamic program: values are
+/// processed left to right (1..=13), and the state at the boundary before
+/// value `v` records, per color, which runs are still "open" — not yet
+/// closed into a finished run — along with how many jokers remain unspent.
+/// Because a color only has two physical copies of any given value, at most
+/// two runs per color can be open at once, which keeps the state small.
+///
+/// At each value, already-open runs of length 1 or 2 *must* be extended (a
+/// run that stops short of length 3 is never valid) — if there isn't a tile
+/// (or spare joker) available to do that, the whole branch is infeasible and
+/// is pruned. Runs already of length >= 3 may optionally keep extending or
+/// be closed into a finished run. Leftover tiles for the value may form up
+/// to two brand-new groups of 3 or 4 colors each. The search is memoized on
+/// `(value, state)` and keeps only the best score (hand tiles placed) seen
+/// for each state.
+///
+/// # Known simplifications
+/// * The score only credits numbered hand tiles; a hand joker spent covering
+///   an open run's mandatory extension isn't distinguished from a board
+///   joker doing the same job, so it doesn't add to the maximized count.
 ///
 /// # Returns
-/// * `Option<(Board, Vec<Tile>, Vec<Tile>)>` - Option containing:
-///   - The board after optimal tile placement
-///   - Vector of tiles that were successfully placed (in placement order)
-///   - Vector of tiles that could not be placed
-///
-/// # Performance
-/// Time complexity is O(n! × placement_cost) where n is the number of tiles.
-/// Use with caution for large tile sets.
-///
-/// # Example
-/// ```
-/// // Given tiles [Blue 6, Orange 4, Blue 1] and a board with runs Blue 2-5, Orange 1-3
-/// // Might try different orders and find Blue 1, Blue 6, Orange 4 places optimally
-/// // Returns (updated_board, [Blue 1, Blue 6, Orange 4])
-/// ```
-pub fn place_tiles_best(board: &Board, tiles: &[Tile]) -> Option<(Board, Vec<Tile>, Vec<Tile>)> {
-    let mut best_board = board.clone();
-    let mut best_tiles = vec![];
-
-    for perm in tiles.iter().permutations(tiles.len()).unique() {
-        let perm_tiles: Vec<Tile> = perm.into_iter().map(|&tile| tile).collect();
-        let (attempt_board, placed_tiles) = place_tiles(board, &perm_tiles);
-
-        // check if the attempted placement is valid
-        if attempt_board.valid() {
-            if placed_tiles.iter().count() > best_tiles.len() {
-                best_board = attempt_board;
-                best_tiles = placed_tiles;
+/// * `Some(Board)` - A board using every board tile and as many hand tiles as possible
+/// * `None` - If some board tile cannot be re-placed at all
+pub fn solve_maximal(board: &Board, hand: &[Tile]) -> Option<Board> {
+    let pool = gather_pool(board, hand);
+
+    let initial_state = DpState {
+        opens: Default::default(),
+        jokers_remaining: pool.jokers,
+    };
+
+    // (hand tiles placed, -runs closed so far): placed tiles is what's being
+    // maximized, but ties there are common (the same tiles can be spent
+    // continuing a run or starting a fresh one), and left to an arbitrary
+    // tie-break they make the DP's output depend on HashMap iteration order
+    // — different runs of the same binary could close an already-long run
+    // early and reopen it right after, splitting one board run into two
+    // adjacent ones. Breaking ties by preferring fewer closures keeps a run
+    // open for as long as it can be, so it stays a single run in the result.
+    type Score = (i32, i32);
+
+    // history[v] holds the best score to reach each state after processing
+    // value v, plus enough to backtrack: the previous state and what closed
+    // or formed along the way. Kept per-value (rather than just the latest
+    // frontier) since backtracking needs to walk all the way back to v=1.
+    type Frontier = HashMap<DpState, (Score, Option<(DpState, Vec<Run>, Vec<Group>)>)>;
+    let mut history: Vec<Frontier> = vec![HashMap::new()];
+    history[0].insert(initial_state, ((0, 0), None));
+
+    for v in 1u8..=13 {
+        let mut next_frontier: Frontier = HashMap::new();
+        let current_frontier = history.last().unwrap();
+
+        for (state, &(score, _)) in current_frontier.iter() {
+            let structural: Vec<u8> = Color::ALL
+                .iter()
+                .enumerate()
+                .map(|(i, _)| {
+                    state.opens[i]
+                        .iter()
+                        .filter(|open| matches!(v - open.start, 1 | 2))
+                        .count() as u8
+                })
+                .collect();
+
+            let real_available: Vec<u8> = Color::ALL
+                .iter()
+                .map(|color| *pool.counts.get(&(*color, v)).unwrap_or(&0))
+                .collect();
+
+            let shortfall: Vec<u8> = (0..4)
+                .map(|i| structural[i].saturating_sub(real_available[i]))
+                .collect();
+            let total_shortfall: u8 = shortfall.iter().sum();
+            if total_shortfall > state.jokers_remaining {
+                continue; // not enough jokers to keep every open run alive
             }
-            if best_tiles.iter().count() == tiles.len() {
-                // All tiles placed, compress runs before returning
-                println!("before compress:\n{}", best_board.to_string());
-                best_board.runs = compress_runs(&best_board.runs);
-                println!("after compress:\n{}", best_board.to_string());
-                return Some((best_board, best_tiles, vec![]));
+            let jokers_after_mandatory = state.jokers_remaining - total_shortfall;
+
+            let real_left_optional: Vec<u8> = (0..4)
+                .map(|i| real_available[i].saturating_sub(structural[i]))
+                .collect();
+
+            let board_mandatory: Vec<u8> = Color::ALL
+                .iter()
+                .map(|color| *pool.mandatory_counts.get(&(*color, v)).unwrap_or(&0))
+                .collect();
+
+            let options: Vec<Vec<ColorTransition>> = Color::ALL
+                .iter()
+                .enumerate()
+                .map(|(i, color)| color_transitions(&state.opens[i], v, *color, real_left_optional[i], shortfall[i]))
+                .collect();
+
+            for ct0 in &options[0] {
+                for ct1 in &options[1] {
+                    for ct2 in &options[2] {
+                        for ct3 in &options[3] {
+                            let cts = [ct0, ct1, ct2, ct3];
+
+                            // Every color must cover its board-mandatory tiles
+                            // for this value using run extensions and/or groups.
+                            // A color can be a member of at most two new groups
+                            // this round (it only has two physical copies), so
+                            // more than two still-uncovered copies is infeasible.
+                            let needed_extra: Vec<u8> = (0..4)
+                                .map(|i| board_mandatory[i].saturating_sub(structural[i] + cts[i].optional_consumed))
+                                .collect();
+                            if needed_extra.iter().any(|n| *n > 2) {
+                                continue;
+                            }
+
+                            let leftover: HashMap<Color, u8> = Color::ALL
+                                .iter()
+                                .enumerate()
+                                .map(|(i, color)| (*color, cts[i].leftover_real))
+                                .collect();
+
+                            for (groups, consumed, jokers_used_group) in
+                                group_options(&leftover, jokers_after_mandatory, v)
+                            {
+                                let mut ok = true;
+                                let mut round_score = 0i32;
+                                let mut next_opens: [Vec<OpenRun>; 4] = Default::default();
+
+                                for i in 0..4 {
+                                    let color = Color::ALL[i];
+                                    let group_membership = *consumed.get(&color).unwrap_or(&0);
+                                    if needed_extra[i] > group_membership {
+                                        ok = false;
+                                        break;
+                                    }
+                                    let total_consumed =
+                                        structural[i] + cts[i].optional_consumed + group_membership;
+                                    round_score += (total_consumed - board_mandatory[i]) as i32;
+                                    next_opens[i] = cts[i].next_opens.clone();
+                                }
+                                if !ok {
+                                    continue;
+                                }
+
+                                let next_state = DpState {
+                                    opens: next_opens,
+                                    jokers_remaining: jokers_after_mandatory - jokers_used_group,
+                                };
+
+                                let mut closed_runs = vec![];
+                                for ct in &cts {
+                                    closed_runs.extend(ct.closed_runs.iter().cloned());
+                                }
+
+                                let new_score: Score = (score.0 + round_score, score.1 - closed_runs.len() as i32);
+                                let should_insert = match next_frontier.get(&next_state) {
+                                    Some((best, _)) => new_score > *best,
+                                    None => true,
+                                };
+                                if should_insert {
+                                    next_frontier.insert(
+                                        next_state,
+                                        (new_score, Some((state.clone(), closed_runs, groups))),
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
             }
         }
+
+        history.push(next_frontier);
+    }
+
+    // Only states where every still-open run has reached length >= 3 (so it
+    // can be finalized as-is) are valid endings, and every mandatory joker
+    // must have been spent somewhere along the way.
+    let max_optional_jokers_left = pool.jokers - pool.mandatory_jokers;
+    let best_final = history
+        .last()
+        .unwrap()
+        .iter()
+        .filter(|(state, _)| state.jokers_remaining <= max_optional_jokers_left)
+        .filter(|(state, _)| {
+            state
+                .opens
+                .iter()
+                .all(|opens| opens.iter().all(|open| 14 - open.start >= 3))
+        })
+        .max_by_key(|(_, (score, _))| *score);
+
+    let (final_state, _) = best_final?;
+    let final_state = final_state.clone();
+
+    // Backtrack through the chosen path, collecting every run/group that was
+    // finalized along the way.
+    let mut runs = vec![];
+    let mut groups = vec![];
+    let mut state = final_state.clone();
+    for frontier in history.iter().rev() {
+        match frontier.get(&state) {
+            Some((_, Some((prev_state, closed_runs, new_groups)))) => {
+                runs.extend(closed_runs.iter().cloned());
+                groups.extend(new_groups.iter().cloned());
+                state = prev_state.clone();
+            }
+            _ => break,
+        }
+    }
+
+    for (i, color) in Color::ALL.iter().enumerate() {
+        for open in &final_state.opens[i] {
+            runs.push(Run {
+                start: open.start,
+                end: 13,
+                color: *color,
+                jokers: open.jokers.iter().copied().collect(),
+            });
+        }
+    }
+
+    Some(Board { groups, runs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn place_tiles_best_extends_two_runs_and_leaves_unusable_tile_unplaced() {
+        let board = Board {
+            groups: vec![],
+            runs: vec![
+                Run { start: 2, end: 5, color: Color::Blue, jokers: HashSet::new() },
+                Run { start: 1, end: 3, color: Color::Orange, jokers: HashSet::new() },
+            ],
+        };
+        let tiles = vec![
+            Tile::Numbered { color: Color::Blue, value: 6 },
+            Tile::Numbered { color: Color::Blue, value: 7 },
+            Tile::Numbered { color: Color::Orange, value: 4 },
+            Tile::Numbered { color: Color::Red, value: 9 },
+        ];
+
+        let (new_board, placed, unplaced) = place_tiles_best(&board, &tiles).unwrap();
+        assert!(new_board.valid());
+        assert_eq!(placed.len(), 3);
+        assert_eq!(unplaced, vec![Tile::Numbered { color: Color::Red, value: 9 }]);
+        // Every value involved has only one physical copy in play, so the DP's
+        // closure tie-break must keep each color merged into a single run
+        // rather than splitting it into adjacent pieces.
+        let blue_runs: Vec<&Run> = new_board.runs.iter().filter(|run| run.color == Color::Blue).collect();
+        assert_eq!(blue_runs.len(), 1);
+        assert_eq!((blue_runs[0].start, blue_runs[0].end), (2, 7));
+
+        let orange_runs: Vec<&Run> = new_board.runs.iter().filter(|run| run.color == Color::Orange).collect();
+        assert_eq!(orange_runs.len(), 1);
+        assert_eq!((orange_runs[0].start, orange_runs[0].end), (1, 4));
     }
 
-    if !best_tiles.is_empty() {
-        // Some tiles were placed, calculate unplaced tiles and compress runs
-        println!("before compress:\n{}", best_board.to_string());
-        best_board.runs = compress_runs(&best_board.runs);
-        let unplaced_tiles: Vec<Tile> = tiles
+    #[test]
+    fn place_tiles_best_returns_none_when_nothing_can_be_placed() {
+        let board = Board {
+            groups: vec![],
+            runs: vec![Run { start: 2, end: 5, color: Color::Blue, jokers: HashSet::new() }],
+        };
+        let tiles = vec![Tile::Numbered { color: Color::Red, value: 9 }];
+
+        assert!(place_tiles_best(&board, &tiles).is_none());
+    }
+
+    #[test]
+    fn solve_maximal_preserves_every_board_tile() {
+        let board = Board {
+            groups: vec![Group {
+                value: 7,
+                colors: vec![Color::Blue, Color::Red, Color::Orange],
+                jokers: HashSet::new(),
+            }],
+            runs: vec![Run { start: 2, end: 5, color: Color::Blue, jokers: HashSet::new() }],
+        };
+        let hand = vec![Tile::Numbered { color: Color::Blue, value: 6 }];
+
+        let new_board = solve_maximal(&board, &hand).unwrap();
+        assert!(new_board.valid());
+
+        let before = board_tiles(&board);
+        let mut after = board_tiles(&new_board);
+        // Every tile that was on the board must still appear somewhere,
+        // even though the hand tile added one more.
+        for tile in &before {
+            let position = after.iter().position(|t| t == tile);
+            assert!(position.is_some(), "missing board tile {:?}", tile);
+            after.remove(position.unwrap());
+        }
+    }
+
+    #[test]
+    fn solve_maximal_tracks_which_slot_of_a_run_is_a_joker() {
+        // Board: blue 1-3 with the middle slot (2) standing in for a joker,
+        // and nothing in hand that could replace it, so the only way to
+        // reconstruct the run is to keep using the board's own joker there.
+        let board = Board {
+            groups: vec![],
+            runs: vec![Run {
+                start: 1,
+                end: 3,
+                color: Color::Blue,
+                jokers: HashSet::from([2]),
+            }],
+        };
+
+        let new_board = solve_maximal(&board, &[]).unwrap();
+        assert!(new_board.valid());
+
+        let run = new_board
+            .runs
             .iter()
-            .filter(|tile| !best_tiles.contains(tile))
-            .cloned()
-            .collect();
-        println!("after compress:\n{}", best_board.to_string());
-        Some((best_board, best_tiles, unplaced_tiles))
-    } else {
-        None
+            .find(|run| run.color == Color::Blue)
+            .unwrap();
+        assert_eq!((run.start, run.end), (1, 3));
+        assert_eq!(run.jokers, HashSet::from([2]));
+    }
+
+    #[test]
+    fn solve_maximal_lets_a_new_group_compete_with_run_continuation() {
+        // Board: a blue run 1-4. Hand: blue5 could extend the run to 1-7
+        // (with blue6/blue7), or go into a same-value group with
+        // red5/orange5/black5. Using every hand tile requires doing both:
+        // the group takes blue5 while the run still extends with
+        // blue6/blue7 onto a fresh joker-free blue5... but there's only one
+        // blue5, so the solver must pick whichever split plays more tiles.
+        let board = Board {
+            groups: vec![],
+            runs: vec![Run { start: 1, end: 4, color: Color::Blue, jokers: HashSet::new() }],
+        };
+        let hand = vec![
+            Tile::Numbered { color: Color::Blue, value: 5 },
+            Tile::Numbered { color: Color::Red, value: 5 },
+            Tile::Numbered { color: Color::Orange, value: 5 },
+            Tile::Numbered { color: Color::Black, value: 5 },
+            Tile::Numbered { color: Color::Blue, value: 6 },
+            Tile::Numbered { color: Color::Blue, value: 7 },
+        ];
+
+        let new_board = solve_maximal(&board, &hand).unwrap();
+        assert!(new_board.valid());
+
+        // The original blue run must still be there in some form (start 1).
+        assert!(new_board.runs.iter().any(|run| run.color == Color::Blue && run.start == 1));
+        // A same-value group covering the other three colors at 5 should
+        // have formed, since that's the only way to place all six hand tiles.
+        assert!(new_board.groups.iter().any(|group| group.value == 5 && group.colors.len() == 3));
+    }
+
+    #[test]
+    fn solve_maximal_reconstructs_two_same_value_groups() {
+        // Blue and Red each have two physical copies of value 1, so two
+        // disjoint-enough groups both at value 1 are a legal, already-valid
+        // board. The DP must still find a decomposition using every tile.
+        let board = Board {
+            groups: vec![
+                Group { value: 1, colors: vec![Color::Blue, Color::Red, Color::Orange], jokers: HashSet::new() },
+                Group { value: 1, colors: vec![Color::Blue, Color::Red, Color::Black], jokers: HashSet::new() },
+            ],
+            runs: vec![],
+        };
+        assert!(board.valid());
+
+        let new_board = solve_maximal(&board, &[]).expect("must reconstruct an already-valid board");
+        assert!(new_board.valid());
+
+        let before = board_tiles(&board);
+        let mut after = board_tiles(&new_board);
+        for tile in &before {
+            let position = after.iter().position(|t| t == tile);
+            assert!(position.is_some(), "missing board tile {:?}", tile);
+            after.remove(position.unwrap());
+        }
+    }
+
+    #[test]
+    fn solve_maximal_merges_an_extended_run_deterministically() {
+        // Two pre-split but adjacent board runs, extended by one more hand
+        // tile per color. Nothing here is duplicated, so the DP should
+        // always merge each color into one run, the same way on every call,
+        // rather than an arbitrary tie-break sometimes leaving it split.
+        let board = Board {
+            groups: vec![],
+            runs: vec![
+                Run { start: 2, end: 5, color: Color::Blue, jokers: HashSet::new() },
+                Run { start: 6, end: 9, color: Color::Blue, jokers: HashSet::new() },
+            ],
+        };
+        let hand = vec![Tile::Numbered { color: Color::Blue, value: 10 }];
+
+        for _ in 0..20 {
+            let new_board = solve_maximal(&board, &hand).unwrap();
+            assert!(new_board.valid());
+            assert_eq!(new_board.runs.len(), 1);
+            assert_eq!((new_board.runs[0].start, new_board.runs[0].end), (2, 10));
+        }
     }
 }
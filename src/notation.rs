@@ -0,0 +1,275 @@
+use crate::structures::{Board, Color, Group, Run, Tile};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Something went wrong turning a piece of text notation into game data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotationError {
+    /// A tile token wasn't a recognized color prefix/name followed by a value.
+    BadTile(String),
+    /// A `run:...` token didn't have the `run:color:start-end` shape.
+    BadRun(String),
+    /// A `group:...` token didn't have the `group:value:color,color,...` shape.
+    BadGroup(String),
+    /// A board token wasn't a `run:...` or `group:...` token at all.
+    UnknownBoardToken(String),
+    /// A color name/letter wasn't one of blue/red/orange/black.
+    BadColor(String),
+    /// A tile value was present but out of the 1-13 range.
+    BadValue(String),
+    /// More than two copies of the same concrete tile were requested.
+    TooManyCopies(Tile),
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotationError::BadTile(token) => write!(f, "'{}' is not a valid tile (expected e.g. 'b3', 'blue3', or 'j' for a joker)", token),
+            NotationError::BadRun(token) => write!(f, "'{}' is not a valid run (expected 'run:color:start-end')", token),
+            NotationError::BadGroup(token) => write!(f, "'{}' is not a valid group (expected 'group:value:color,color,color')", token),
+            NotationError::UnknownBoardToken(token) => write!(f, "'{}' is not a 'run:...' or 'group:...' token", token),
+            NotationError::BadColor(token) => write!(f, "'{}' is not a known color (expected blue/red/orange/black, or b/r/o/k)", token),
+            NotationError::BadValue(token) => write!(f, "'{}' is not a valid tile value (expected 1-13)", token),
+            NotationError::TooManyCopies(tile) => write!(f, "more than two copies of {:?} were requested", tile),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+/// Parse a color from either its full name (`Color::from_str`) or the
+/// single-letter abbreviations used by compact tile tokens: `b`lue, `r`ed,
+/// `o`range, blac`k`.
+fn parse_color(s: &str) -> Result<Color, NotationError> {
+    if let Some(color) = Color::from_str(s) {
+        return Ok(color);
+    }
+
+    match s.to_lowercase().as_str() {
+        "b" => Ok(Color::Blue),
+        "r" => Ok(Color::Red),
+        "o" => Ok(Color::Orange),
+        "k" => Ok(Color::Black),
+        _ => Err(NotationError::BadColor(s.to_string())),
+    }
+}
+
+fn parse_value(s: &str) -> Result<u8, NotationError> {
+    let value: u8 = s.parse().map_err(|_| NotationError::BadValue(s.to_string()))?;
+    if (1..=13).contains(&value) {
+        Ok(value)
+    } else {
+        Err(NotationError::BadValue(s.to_string()))
+    }
+}
+
+/// Parse a single tile token: `j`/`joker` for a wildcard, or a color prefix
+/// (letter or full name) directly followed by a value, e.g. `b3` or `blue3`.
+pub fn parse_tile(token: &str) -> Result<Tile, NotationError> {
+    let trimmed = token.trim();
+    if trimmed.eq_ignore_ascii_case("j") || trimmed.eq_ignore_ascii_case("joker") {
+        return Ok(Tile::Joker { standing_in: None });
+    }
+
+    let split_at = trimmed
+        .find(|c: char| c.is_ascii_digit())
+        .ok_or_else(|| NotationError::BadTile(token.to_string()))?;
+    let (color_part, value_part) = trimmed.split_at(split_at);
+    if color_part.is_empty() || value_part.is_empty() {
+        return Err(NotationError::BadTile(token.to_string()));
+    }
+
+    let color = parse_color(color_part).map_err(|_| NotationError::BadTile(token.to_string()))?;
+    let value = parse_value(value_part).map_err(|_| NotationError::BadTile(token.to_string()))?;
+    Ok(Tile::Numbered { color, value })
+}
+
+/// Parse a whitespace-separated list of tile tokens into a hand, rejecting
+/// hands that ask for more than two copies of the same concrete tile (a
+/// standard Rummikub set only has two of each).
+pub fn parse_hand(s: &str) -> Result<Vec<Tile>, NotationError> {
+    let mut tiles = vec![];
+    let mut counts: std::collections::HashMap<Tile, u8> = std::collections::HashMap::new();
+
+    for token in s.split_whitespace() {
+        let tile = parse_tile(token)?;
+        let count = counts.entry(tile).or_insert(0);
+        *count += 1;
+        if *count > 2 {
+            return Err(NotationError::TooManyCopies(tile));
+        }
+        tiles.push(tile);
+    }
+
+    Ok(tiles)
+}
+
+/// Parse a `run:color:start-end` token, e.g. `run:blue:2-5`.
+pub fn parse_run(token: &str) -> Result<Run, NotationError> {
+    let bad = || NotationError::BadRun(token.to_string());
+
+    let rest = token.strip_prefix("run:").ok_or_else(bad)?;
+    let (color_part, range_part) = rest.split_once(':').ok_or_else(bad)?;
+    let (start_part, end_part) = range_part.split_once('-').ok_or_else(bad)?;
+
+    let color = parse_color(color_part).map_err(|_| bad())?;
+    let start = parse_value(start_part).map_err(|_| bad())?;
+    let end = parse_value(end_part).map_err(|_| bad())?;
+
+    let run = Run {
+        start,
+        end,
+        color,
+        jokers: HashSet::new(),
+    };
+    if !run.valid() {
+        return Err(bad());
+    }
+    Ok(run)
+}
+
+/// Parse a `group:value:color,color,color[,color]` token, e.g.
+/// `group:10:orange,blue,red`.
+pub fn parse_group(token: &str) -> Result<Group, NotationError> {
+    let bad = || NotationError::BadGroup(token.to_string());
+
+    let rest = token.strip_prefix("group:").ok_or_else(bad)?;
+    let (value_part, colors_part) = rest.split_once(':').ok_or_else(bad)?;
+
+    let value = parse_value(value_part).map_err(|_| bad())?;
+    let colors: Vec<Color> = colors_part
+        .split(',')
+        .map(|c| parse_color(c).map_err(|_| bad()))
+        .collect::<Result<_, _>>()?;
+
+    if colors.len() < 3 || colors.len() > 4 {
+        return Err(bad());
+    }
+
+    let group = Group {
+        value,
+        colors,
+        jokers: HashSet::new(),
+    };
+    if !group.valid() {
+        return Err(bad());
+    }
+    Ok(group)
+}
+
+/// Parse a whitespace-separated list of `run:...` and `group:...` tokens into
+/// a board.
+pub fn parse_board(s: &str) -> Result<Board, NotationError> {
+    let mut board = Board {
+        groups: vec![],
+        runs: vec![],
+    };
+
+    for token in s.split_whitespace() {
+        if token.starts_with("run:") {
+            board.runs.push(parse_run(token)?);
+        } else if token.starts_with("group:") {
+            board.groups.push(parse_group(token)?);
+        } else {
+            return Err(NotationError::UnknownBoardToken(token.to_string()));
+        }
+    }
+
+    Ok(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_color_accepts_full_names_and_single_letters() {
+        assert_eq!(parse_color("blue"), Ok(Color::Blue));
+        assert_eq!(parse_color("Blue"), Ok(Color::Blue));
+        assert_eq!(parse_color("b"), Ok(Color::Blue));
+        assert_eq!(parse_color("k"), Ok(Color::Black));
+    }
+
+    #[test]
+    fn parse_color_rejects_unknown_names() {
+        assert_eq!(parse_color("purple"), Err(NotationError::BadColor("purple".to_string())));
+    }
+
+    #[test]
+    fn parse_value_rejects_out_of_range_and_non_numeric() {
+        assert_eq!(parse_value("0"), Err(NotationError::BadValue("0".to_string())));
+        assert_eq!(parse_value("14"), Err(NotationError::BadValue("14".to_string())));
+        assert_eq!(parse_value("x"), Err(NotationError::BadValue("x".to_string())));
+        assert_eq!(parse_value("7"), Ok(7));
+    }
+
+    #[test]
+    fn parse_tile_accepts_letter_and_full_color_prefixes() {
+        assert_eq!(parse_tile("b3"), Ok(Tile::Numbered { color: Color::Blue, value: 3 }));
+        assert_eq!(parse_tile("blue3"), Ok(Tile::Numbered { color: Color::Blue, value: 3 }));
+        assert_eq!(parse_tile("j"), Ok(Tile::Joker { standing_in: None }));
+        assert_eq!(parse_tile("joker"), Ok(Tile::Joker { standing_in: None }));
+    }
+
+    #[test]
+    fn parse_tile_rejects_malformed_tokens() {
+        assert_eq!(parse_tile("blue"), Err(NotationError::BadTile("blue".to_string())));
+        assert_eq!(parse_tile("3"), Err(NotationError::BadTile("3".to_string())));
+        assert_eq!(parse_tile("purple3"), Err(NotationError::BadTile("purple3".to_string())));
+        assert_eq!(parse_tile("b14"), Err(NotationError::BadTile("b14".to_string())));
+    }
+
+    #[test]
+    fn parse_hand_rejects_more_than_two_copies_of_a_tile() {
+        let tile = Tile::Numbered { color: Color::Blue, value: 3 };
+        assert_eq!(parse_hand("b3 b3 b3"), Err(NotationError::TooManyCopies(tile)));
+        assert!(parse_hand("b3 b3").is_ok());
+    }
+
+    #[test]
+    fn parse_run_accepts_well_formed_tokens() {
+        let run = parse_run("run:blue:2-5").unwrap();
+        assert_eq!(run, Run { start: 2, end: 5, color: Color::Blue, jokers: HashSet::new() });
+    }
+
+    #[test]
+    fn parse_run_rejects_malformed_and_invalid_tokens() {
+        assert_eq!(parse_run("blue:2-5"), Err(NotationError::BadRun("blue:2-5".to_string())));
+        assert_eq!(parse_run("run:blue:25"), Err(NotationError::BadRun("run:blue:25".to_string())));
+        assert_eq!(parse_run("run:purple:2-5"), Err(NotationError::BadRun("run:purple:2-5".to_string())));
+        // A run must be at least 3 tiles long, so a single-tile "run" is invalid.
+        assert_eq!(parse_run("run:blue:5-5"), Err(NotationError::BadRun("run:blue:5-5".to_string())));
+    }
+
+    #[test]
+    fn parse_group_accepts_well_formed_tokens() {
+        let group = parse_group("group:10:orange,blue,red").unwrap();
+        assert_eq!(group.value, 10);
+        assert_eq!(group.colors, vec![Color::Orange, Color::Blue, Color::Red]);
+    }
+
+    #[test]
+    fn parse_group_rejects_malformed_and_wrong_size_tokens() {
+        assert_eq!(parse_group("10:orange,blue,red"), Err(NotationError::BadGroup("10:orange,blue,red".to_string())));
+        assert_eq!(
+            parse_group("group:10:orange,blue"),
+            Err(NotationError::BadGroup("group:10:orange,blue".to_string()))
+        );
+        assert_eq!(
+            parse_group("group:200:orange,blue,red"),
+            Err(NotationError::BadGroup("group:200:orange,blue,red".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_board_combines_runs_and_groups_in_order() {
+        let board = parse_board("run:blue:2-5 group:10:orange,blue,red").unwrap();
+        assert_eq!(board.runs.len(), 1);
+        assert_eq!(board.groups.len(), 1);
+    }
+
+    #[test]
+    fn parse_board_rejects_unknown_tokens() {
+        assert_eq!(parse_board("blue3"), Err(NotationError::UnknownBoardToken("blue3".to_string())));
+    }
+}
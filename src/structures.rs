@@ -10,6 +10,8 @@ pub enum Color {
 }
 
 impl Color {
+    pub const ALL: [Color; 4] = [Color::Blue, Color::Red, Color::Orange, Color::Black];
+
     pub fn from_str(s: &str) -> Option<Color> {
         match s.to_lowercase().as_str() {
             "blue" => Some(Color::Blue),
@@ -30,27 +32,81 @@ impl Color {
         }
     }
 }
+
+/// A single playable piece: either a concrete numbered tile, or a joker.
+///
+/// A joker that is sitting in a player's hand doesn't represent anything yet
+/// (`standing_in` is `None`). Once it is placed into a `Run` or `Group` it
+/// records the color/value it is substituting for, so the board can still be
+/// displayed and validated as if every slot held a real tile.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-pub struct Tile {
-    pub color: Color,
-    pub value: u8,
+pub enum Tile {
+    Numbered { color: Color, value: u8 },
+    Joker { standing_in: Option<(Color, u8)> },
+}
+
+impl Tile {
+    /// The color this tile occupies on the board, if any.
+    ///
+    /// For a `Numbered` tile this is always `Some`. For a `Joker` it is the
+    /// color it stands in for once placed, or `None` while it's still an
+    /// unplaced wildcard in a hand.
+    pub fn color(&self) -> Option<Color> {
+        match self {
+            Tile::Numbered { color, .. } => Some(*color),
+            Tile::Joker { standing_in } => standing_in.map(|(color, _)| color),
+        }
+    }
+
+    /// The value this tile occupies on the board, if any. See [`Tile::color`].
+    pub fn value(&self) -> Option<u8> {
+        match self {
+            Tile::Numbered { value, .. } => Some(*value),
+            Tile::Joker { standing_in } => standing_in.map(|(_, value)| value),
+        }
+    }
+
+    pub fn is_joker(&self) -> bool {
+        matches!(self, Tile::Joker { .. })
+    }
 }
 
 impl fmt::Display for Tile {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let ansi_code: &str = self.color.bg_ansi_code();
-        let value: u8 = self.value;
-        write!(f, "{}{:2}\x1b[0m", ansi_code, value)
+        match self {
+            Tile::Numbered { color, value } => {
+                let ansi_code: &str = color.bg_ansi_code();
+                write!(f, "{}{:2}\x1b[0m", ansi_code, value)
+            }
+            Tile::Joker {
+                standing_in: Some((color, value)),
+            } => {
+                // A joker standing in for a real tile renders like that tile,
+                // marked with a trailing `*` so it's still clear it's a joker.
+                let ansi_code: &str = color.bg_ansi_code();
+                write!(f, "{}{:2}*\x1b[0m", ansi_code, value)
+            }
+            Tile::Joker { standing_in: None } => write!(f, "\x1b[47m J\x1b[0m"),
+        }
     }
 }
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Group {
     pub value: u8,
     pub colors: Vec<Color>,
+    /// Colors in `colors` that are physically a joker standing in for that color.
+    pub jokers: HashSet<Color>,
 }
 
 impl Group {
     pub fn valid(&self) -> bool {
+        if !(1..=13).contains(&self.value) {
+            return false;
+        }
+        if self.colors.len() < 3 || self.colors.len() > 4 {
+            return false;
+        }
         let mut seen = HashSet::new();
         self.colors.iter().all(|x| seen.insert(x))
     }
@@ -63,9 +119,15 @@ impl fmt::Display for Group {
             write!(f, "empty")
         } else {
             Ok(for (index, color) in self.colors.iter().enumerate() {
-                let tile = Tile {
-                    color: *color,
-                    value: self.value,
+                let tile = if self.jokers.contains(color) {
+                    Tile::Joker {
+                        standing_in: Some((*color, self.value)),
+                    }
+                } else {
+                    Tile::Numbered {
+                        color: *color,
+                        value: self.value,
+                    }
                 };
 
                 if index > 0 {
@@ -77,16 +139,19 @@ impl fmt::Display for Group {
         }
     }
 }
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Run {
     pub start: u8,
     pub end: u8,
     pub color: Color,
+    /// Values in `start..=end` that are physically a joker standing in for that value.
+    pub jokers: HashSet<u8>,
 }
 
 impl Run {
     pub fn valid(&self) -> bool {
-        self.start < self.end && self.end - self.start >= 2
+        self.start >= 1 && self.end <= 13 && self.start < self.end && self.end - self.start >= 2
     }
 }
 
@@ -99,9 +164,15 @@ impl fmt::Display for Run {
                 write!(f, " ")?;
                 // write!(f, " → ")?;
             }
-            let tile = Tile {
-                color: self.color,
-                value: *value,
+            let tile = if self.jokers.contains(value) {
+                Tile::Joker {
+                    standing_in: Some((self.color, *value)),
+                }
+            } else {
+                Tile::Numbered {
+                    color: self.color,
+                    value: *value,
+                }
             };
 
             write!(f, "{}", tile.to_string())?;
@@ -109,7 +180,7 @@ impl fmt::Display for Run {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Board {
     pub groups: Vec<Group>,
     pub runs: Vec<Run>,
@@ -148,3 +219,82 @@ impl fmt::Display for Board {
 pub struct Player {
     pub tiles: Vec<Tile>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_rejects_out_of_range_bounds() {
+        let run = Run {
+            start: 0,
+            end: 3,
+            color: Color::Blue,
+            jokers: HashSet::new(),
+        };
+        assert!(!run.valid());
+
+        let run = Run {
+            start: 12,
+            end: 14,
+            color: Color::Blue,
+            jokers: HashSet::new(),
+        };
+        assert!(!run.valid());
+    }
+
+    #[test]
+    fn run_accepts_in_range_run_of_three_or_more() {
+        let run = Run {
+            start: 1,
+            end: 13,
+            color: Color::Blue,
+            jokers: HashSet::new(),
+        };
+        assert!(run.valid());
+    }
+
+    #[test]
+    fn group_rejects_wrong_size() {
+        let two_colors = Group {
+            value: 5,
+            colors: vec![Color::Blue, Color::Red],
+            jokers: HashSet::new(),
+        };
+        assert!(!two_colors.valid());
+
+        let all_four = Group {
+            value: 5,
+            colors: vec![Color::Blue, Color::Red, Color::Orange, Color::Black],
+            jokers: HashSet::new(),
+        };
+        assert!(all_four.valid());
+    }
+
+    #[test]
+    fn group_rejects_duplicate_colors() {
+        let group = Group {
+            value: 5,
+            colors: vec![Color::Blue, Color::Blue, Color::Red],
+            jokers: HashSet::new(),
+        };
+        assert!(!group.valid());
+    }
+
+    #[test]
+    fn group_rejects_out_of_range_value() {
+        let group = Group {
+            value: 200,
+            colors: vec![Color::Blue, Color::Red, Color::Orange],
+            jokers: HashSet::new(),
+        };
+        assert!(!group.valid());
+
+        let group = Group {
+            value: 0,
+            colors: vec![Color::Blue, Color::Red, Color::Orange],
+            jokers: HashSet::new(),
+        };
+        assert!(!group.valid());
+    }
+}